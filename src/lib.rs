@@ -11,10 +11,69 @@ pub use types::*;
 
 pub mod x86_64;
 
+pub mod riscv;
+
+/// A single page table entry, abstracted over the architecture-specific
+/// bit layout so that [`Mapper`]'s high-level methods can walk, create
+/// and tear down mappings generically.
+pub trait PageEntry {
+    /// Reset the entry to an empty, not-present state.
+    fn clear(&mut self);
+
+    /// Physical address referenced by this entry.
+    fn address(&self) -> PhysicalAddress;
+
+    /// Set the physical address referenced by this entry.
+    fn set_address(&mut self, address: PhysicalAddress) -> &mut Self;
+
+    /// Whether this entry currently points at a mapping or a subtable.
+    fn is_present(&self) -> bool;
+
+    /// Mark the entry present or not present.
+    fn set_present(&mut self, present: bool) -> &mut Self;
+
+    /// Whether this entry is a huge/block leaf rather than a pointer to
+    /// a subtable.
+    fn is_huge(&self) -> bool;
+
+    /// Mark the entry as a huge/block leaf or as a pointer to a
+    /// subtable.
+    fn set_huge(&mut self, huge: bool) -> &mut Self;
+}
+
+/// Number of address bits translated by a single page table level.
+const LEVEL_BITS: u32 = 9;
+
+/// Size in bytes of the region mapped by a single entry at `level`
+/// (level 1 is the lowest, leaf-only level).
+pub(crate) fn level_size(level: u8) -> u64 {
+    0x1000u64 << (LEVEL_BITS * (level as u32 - 1))
+}
+
 /// Provides access to page table entries.
 pub trait Mapper {
-    /// Page table entry type
-    type Entry;
+    /// Page table entry type. Bound by `'static` because [`entry`](Mapper::entry)
+    /// and the default methods built on it hand back `&'static mut`
+    /// references (page table entries live for the lifetime of the
+    /// mapping, not of the `&mut self` borrow used to reach them).
+    type Entry: PageEntry + 'static;
+
+    /// Number of page table levels for this architecture, e.g. 4 for
+    /// x86_64 or Sv48, 3 for Sv39.
+    const LEVELS: u8;
+
+    /// Highest level at which an entry may be a huge/block leaf rather
+    /// than a pointer to a subtable, consulted by
+    /// [`map`](Mapper::map)/[`map_range`](Mapper::map_range) instead of
+    /// assuming every level above 1 can be a leaf.
+    ///
+    /// Defaults to capping at level 3 (1 GiB) once `LEVELS` exceeds it.
+    /// This matters on x86_64, where the top-level PML4 entry has no
+    /// valid huge/PS bit at all -- it is hardware-reserved and must
+    /// stay 0 -- so `LEVELS = 4` must not imply a leaf is valid at
+    /// level 4. Architectures whose top level genuinely can be a leaf
+    /// should override this to `Self::LEVELS`.
+    const MAX_HUGE_LEVEL: u8 = if Self::LEVELS > 3 { 3 } else { Self::LEVELS };
 
     /// Get the page table entry for a virtual address.
     ///
@@ -26,4 +85,212 @@ pub trait Mapper {
         virt_addr: VirtualAddress,
         level: u8,
     ) -> Result<&'static mut Self::Entry>;
+
+    /// Whether [`map`](Mapper::map)/[`unmap`](Mapper::unmap) should
+    /// automatically invalidate the affected translation after changing
+    /// an entry. Defaults to `true`; use
+    /// [`set_batch_mode`](Mapper::set_batch_mode) to disable this while
+    /// building many mappings at once, then call a single
+    /// [`flush_all`](Mapper::flush_all) once done.
+    fn flush_enabled(&self) -> bool {
+        true
+    }
+
+    /// Enable or disable automatic per-address flushing, see
+    /// [`flush_enabled`](Mapper::flush_enabled).
+    ///
+    /// The default implementation does nothing, i.e. mappers that don't
+    /// override it always flush eagerly (which is a safe, if not
+    /// maximally fast, default).
+    fn set_batch_mode(&mut self, _batch: bool) {}
+
+    /// Invalidate any cached translation for `virt_addr`.
+    ///
+    /// The default implementation does nothing. Architectures gate
+    /// their actual invalidation instruction behind the `flush`
+    /// feature, so that code built for testing on a different target
+    /// still compiles.
+    fn invalidate(&mut self, _virt_addr: VirtualAddress) {}
+
+    /// Invalidate all cached translations.
+    ///
+    /// See [`invalidate`](Mapper::invalidate) for the feature-gating
+    /// rationale.
+    fn flush_all(&mut self) {}
+
+    /// Called by [`unmap`](Mapper::unmap) once for each level above a
+    /// freshly cleared leaf entry, innermost first. Implementations
+    /// that want to reclaim page tables that became entirely empty
+    /// should inspect the subtable referenced by the `level` entry
+    /// covering `virt_addr` and, if empty, clear that entry and release
+    /// its frame, returning `true` so the caller keeps walking upwards.
+    /// `virt_addr` is passed as given to `unmap`, so it has to be
+    /// aligned down to the level's page size before calling
+    /// [`entry`](Mapper::entry).
+    ///
+    /// The default implementation never reclaims anything.
+    ///
+    /// # Safety
+    ///
+    /// Same caveats as [`entry`](Mapper::entry).
+    unsafe fn reclaim(&mut self, _virt_addr: VirtualAddress, _level: u8) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// Map `virt_addr` to `phys_addr` at the given level, applying
+    /// `flags` to the entry before marking it present.
+    ///
+    /// Fails with [`Error::Overlap`] if the target entry is already
+    /// present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level > 1 && level > Self::MAX_HUGE_LEVEL`, i.e. if
+    /// `level` names a level that has no huge/block leaf encoding on
+    /// this architecture (e.g. x86_64's PML4).
+    ///
+    /// # Safety
+    ///
+    /// Same caveats as [`entry`](Mapper::entry).
+    unsafe fn map(
+        &mut self,
+        virt_addr: VirtualAddress,
+        phys_addr: PhysicalAddress,
+        level: u8,
+        flags: impl FnOnce(&mut Self::Entry),
+    ) -> Result<()> {
+        assert!(level == 1 || level <= Self::MAX_HUGE_LEVEL);
+
+        let entry = self.entry(virt_addr, level)?;
+        if entry.is_present() {
+            return Err(Error::Overlap);
+        }
+
+        entry.set_address(phys_addr);
+        if level > 1 {
+            entry.set_huge(true);
+        }
+        flags(entry);
+        entry.set_present(true);
+
+        if self.flush_enabled() {
+            self.invalidate(virt_addr);
+        }
+        Ok(())
+    }
+
+    /// Map a contiguous `size` byte range starting at `virt_start` to
+    /// `phys_start`, greedily using the largest huge/block page size
+    /// that is aligned and fits at each step (1 GiB, then 2 MiB, then
+    /// falling back to regular 4 KiB pages), applying `flags` to every
+    /// entry created.
+    ///
+    /// # Safety
+    ///
+    /// Same caveats as [`entry`](Mapper::entry).
+    unsafe fn map_range(
+        &mut self,
+        virt_start: VirtualAddress,
+        phys_start: PhysicalAddress,
+        size: u64,
+        flags: impl Fn(&mut Self::Entry),
+    ) -> Result<()> {
+        // The loop below always advances by a full page at whatever
+        // level it picks, so a `size` that isn't a multiple of the base
+        // page size would make the final level 1 page overshoot past
+        // `virt_start + size` into memory the caller never asked for.
+        assert_eq!(size % level_size(1), 0);
+
+        let max_level = Self::MAX_HUGE_LEVEL;
+
+        let mut offset = 0;
+        while offset < size {
+            let virt_addr = virt_start.as_u64() + offset;
+            let phys_addr = phys_start.as_u64() + offset;
+            let remaining = size - offset;
+
+            let mut level = max_level;
+            while level > 1 {
+                let page_size = level_size(level);
+                if virt_addr % page_size == 0 && phys_addr % page_size == 0 && remaining >= page_size {
+                    break;
+                }
+                level -= 1;
+            }
+
+            self.map(virt_addr.into(), phys_addr.into(), level, &flags)?;
+            offset += level_size(level);
+        }
+
+        Ok(())
+    }
+
+    /// Remove the mapping covering `virt_addr`, returning the physical
+    /// frame it referenced along with the level of the entry that
+    /// mapped it (1 for a regular page, >1 for a huge/block mapping),
+    /// mirroring [`translate`](Mapper::translate). Callers must consult
+    /// the level to know how much memory was actually freed -- a level
+    /// 2 frame, for instance, is 2 MiB, not 4 KiB. Transparently
+    /// handles huge/block mappings at any level and reclaims
+    /// intermediate page tables that become empty as a result (see
+    /// [`reclaim`](Mapper::reclaim)).
+    ///
+    /// Fails with [`Error::NotMapped`] if `virt_addr` is not mapped.
+    ///
+    /// # Safety
+    ///
+    /// Same caveats as [`entry`](Mapper::entry).
+    unsafe fn unmap(&mut self, virt_addr: VirtualAddress) -> Result<(PhysicalAddress, u8)> {
+        let mut level = Self::LEVELS;
+        let phys_addr = loop {
+            let aligned = VirtualAddress::new(virt_addr.as_u64() & !(level_size(level) - 1));
+            let entry = self.entry(aligned, level)?;
+            if !entry.is_present() {
+                return Err(Error::NotMapped);
+            }
+            if level == 1 || entry.is_huge() {
+                let phys_addr = entry.address();
+                entry.clear();
+                break phys_addr;
+            }
+            level -= 1;
+        };
+
+        if self.flush_enabled() {
+            self.invalidate(virt_addr);
+        }
+
+        for parent_level in (level + 1)..=Self::LEVELS {
+            if !self.reclaim(virt_addr, parent_level)? {
+                break;
+            }
+        }
+
+        Ok((phys_addr, level))
+    }
+
+    /// Resolve `virt_addr` to the physical address it is mapped to,
+    /// along with the level of the entry that mapped it (1 for a
+    /// regular page, >1 for a huge/block mapping). Returns `None` if
+    /// `virt_addr` is not mapped.
+    ///
+    /// # Safety
+    ///
+    /// Same caveats as [`entry`](Mapper::entry).
+    unsafe fn translate(&mut self, virt_addr: VirtualAddress) -> Option<(PhysicalAddress, u8)> {
+        let mut level = Self::LEVELS;
+        loop {
+            let aligned = VirtualAddress::new(virt_addr.as_u64() & !(level_size(level) - 1));
+            let entry = self.entry(aligned, level).ok()?;
+            if !entry.is_present() {
+                return None;
+            }
+            if level == 1 || entry.is_huge() {
+                let offset = virt_addr.as_u64() % level_size(level);
+                let phys_addr = (entry.address().as_u64() + offset).into();
+                return Some((phys_addr, level));
+            }
+            level -= 1;
+        }
+    }
 }
@@ -6,4 +6,5 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub enum Error {
     NoMemory,
     Overlap,
+    NotMapped,
 }
@@ -0,0 +1,14 @@
+//! RISC-V Sv39/Sv48 specific structures
+use super::*;
+
+mod page_table;
+pub use page_table::*;
+
+mod sv_mapper;
+pub use sv_mapper::*;
+
+mod sv39_mapper;
+pub use sv39_mapper::*;
+
+mod sv48_mapper;
+pub use sv48_mapper::*;
@@ -0,0 +1,259 @@
+use super::*;
+
+/// Defines a mapping from virtual to physical address space.
+#[repr(align(4096))]
+pub struct PageTable {
+    pub entries: [Entry; 512],
+}
+
+/// Page table entry.
+#[derive(Debug, Clone, Copy)]
+pub struct Entry {
+    entry: u64,
+}
+
+/// Properties of a page table entry.
+#[repr(u64)]
+#[derive(Debug, Clone, Copy)]
+pub enum Bit {
+    Valid = 0,
+    Read = 1,
+    Write = 2,
+    Execute = 3,
+    User = 4,
+    Global = 5,
+    Accessed = 6,
+    Dirty = 7,
+}
+
+add_indexing!(PageTable, Entry);
+
+impl PageTable {
+    /// Create a new PageTable.
+    pub const fn new() -> Self {
+        PageTable {
+            entries: [Entry::new(); 512],
+        }
+    }
+
+    /// Clear the page table.
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            entry.clear();
+        }
+    }
+}
+
+impl Entry {
+    /// Create a new page table entry.
+    pub const fn new() -> Self {
+        Entry { entry: 0 }
+    }
+
+    /// Volatile read of the entry.
+    fn read(&self) -> u64 {
+        let entry_ptr = &self.entry as *const u64;
+        unsafe { core::ptr::read_volatile(entry_ptr) }
+    }
+
+    /// Volatile write of the entry.
+    fn write(&mut self, entry: u64) {
+        let entry_ptr = &mut self.entry as *mut u64;
+        unsafe {
+            core::ptr::write_volatile(entry_ptr, entry);
+        }
+    }
+
+    /// Update the entry value using volatile read/write.
+    fn update<F>(&mut self, f: F)
+    where
+        F: Fn(u64) -> u64,
+    {
+        self.write(f(self.read()));
+    }
+
+    /// Set the entry to 0.
+    pub fn clear(&mut self) {
+        self.write(0);
+    }
+
+    /// Physical memory address referenced by this entry.
+    ///
+    /// The PPN occupies bits 10..54 of the entry and is shifted left by
+    /// 12 bits to form the physical address it points at.
+    pub fn address(&self) -> PhysicalAddress {
+        let ppn = (self.read() >> 10) & ((1 << 44) - 1);
+        (ppn << 12).into()
+    }
+
+    /// Set the physical memory address of this entry.
+    pub fn set_address(&mut self, address: PhysicalAddress) -> &mut Self {
+        assert!(address % 0x1000 == 0);
+        let ppn = (address.as_u64() >> 12) & ((1 << 44) - 1);
+        self.update(|entry| (entry & !(((1u64 << 44) - 1) << 10)) | (ppn << 10));
+        self
+    }
+
+    /// RSW bits reserved for use by the operating system.
+    pub fn avail(&self) -> u8 {
+        ((self.read() >> 8) & 0x3) as u8
+    }
+
+    /// Set the RSW bits.
+    pub fn set_avail(&mut self, val: u8) -> &mut Self {
+        if val > 3 {
+            panic!("Avail value out ouf bounds");
+        }
+        self.update(|entry| (entry & !0x300) | ((val as u64) << 8));
+        self
+    }
+
+    /// Whether a certain bit is set.
+    pub fn bit(&self, bit: Bit) -> bool {
+        get_bit!(self.read(), bit as u64)
+    }
+
+    /// Set or unset a bit.
+    fn modify_bit(&mut self, bit: Bit, val: bool) {
+        self.update(|mut entry| {
+            set_bit!(entry, bit as u64, val);
+            entry
+        });
+    }
+
+    /// Set a bit.
+    pub fn set_bit(&mut self, bit: Bit) -> &mut Self {
+        self.modify_bit(bit, true);
+        self
+    }
+
+    /// Unset a bit.
+    pub fn unset_bit(&mut self, bit: Bit) -> &mut Self {
+        self.modify_bit(bit, false);
+        self
+    }
+
+    /// Whether this entry is a leaf descriptor (maps a page directly)
+    /// rather than a pointer to the next-level table. A descriptor is a
+    /// leaf when any of Read/Write/Execute is set.
+    pub fn is_leaf(&self) -> bool {
+        self.bit(Bit::Read) || self.bit(Bit::Write) || self.bit(Bit::Execute)
+    }
+}
+
+impl PageEntry for Entry {
+    fn clear(&mut self) {
+        Entry::clear(self);
+    }
+
+    fn address(&self) -> PhysicalAddress {
+        Entry::address(self)
+    }
+
+    fn set_address(&mut self, address: PhysicalAddress) -> &mut Self {
+        Entry::set_address(self, address)
+    }
+
+    fn is_present(&self) -> bool {
+        self.bit(Bit::Valid)
+    }
+
+    fn set_present(&mut self, present: bool) -> &mut Self {
+        if present {
+            self.set_bit(Bit::Valid)
+        } else {
+            self.unset_bit(Bit::Valid)
+        }
+    }
+
+    fn is_huge(&self) -> bool {
+        self.is_leaf()
+    }
+
+    fn set_huge(&mut self, huge: bool) -> &mut Self {
+        if huge {
+            self.set_bit(Bit::Read).set_bit(Bit::Write)
+        } else {
+            self.unset_bit(Bit::Read).unset_bit(Bit::Write)
+        }
+    }
+}
+
+#[test]
+fn int_consistency() {
+    let mut entry = Entry::new();
+    entry.set_address(0x4242000.into());
+    assert_eq!(entry.address().as_u64(), 0x4242000);
+
+    entry.set_avail(3);
+    assert_eq!(entry.avail(), 3);
+    entry.set_avail(0);
+    assert_eq!(entry.avail(), 0);
+}
+
+#[test]
+fn bit_consistency() {
+    let mut entry = Entry::new();
+    entry.set_address(0x0000_1234_5678_9000.into());
+    assert!(!entry.bit(Bit::Valid));
+    assert!(!entry.bit(Bit::Read));
+    assert!(!entry.bit(Bit::Write));
+    assert!(!entry.bit(Bit::Execute));
+    assert!(!entry.bit(Bit::User));
+    assert!(!entry.bit(Bit::Global));
+    assert!(!entry.bit(Bit::Accessed));
+    assert!(!entry.bit(Bit::Dirty));
+
+    entry.set_bit(Bit::Valid);
+    assert!(entry.bit(Bit::Valid));
+    entry.unset_bit(Bit::Valid);
+
+    entry.set_bit(Bit::Read);
+    assert!(entry.bit(Bit::Read));
+    entry.unset_bit(Bit::Read);
+
+    entry.set_bit(Bit::Write);
+    assert!(entry.bit(Bit::Write));
+    entry.unset_bit(Bit::Write);
+
+    entry.set_bit(Bit::Execute);
+    assert!(entry.bit(Bit::Execute));
+    entry.unset_bit(Bit::Execute);
+
+    entry.set_bit(Bit::User);
+    assert!(entry.bit(Bit::User));
+    entry.unset_bit(Bit::User);
+
+    entry.set_bit(Bit::Global);
+    assert!(entry.bit(Bit::Global));
+    entry.unset_bit(Bit::Global);
+
+    entry.set_bit(Bit::Accessed);
+    assert!(entry.bit(Bit::Accessed));
+    entry.unset_bit(Bit::Accessed);
+
+    entry.set_bit(Bit::Dirty);
+    assert!(entry.bit(Bit::Dirty));
+    entry.unset_bit(Bit::Dirty);
+}
+
+#[test]
+fn leaf_detection() {
+    let mut entry = Entry::new();
+    assert!(!entry.is_leaf());
+
+    entry.set_bit(Bit::Read);
+    assert!(entry.is_leaf());
+    entry.unset_bit(Bit::Read);
+
+    entry.set_bit(Bit::Write);
+    assert!(entry.is_leaf());
+    entry.unset_bit(Bit::Write);
+
+    entry.set_bit(Bit::Execute);
+    assert!(entry.is_leaf());
+    entry.unset_bit(Bit::Execute);
+
+    entry.set_bit(Bit::Valid);
+    assert!(!entry.is_leaf());
+}
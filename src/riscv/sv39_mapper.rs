@@ -0,0 +1,91 @@
+use super::*;
+
+/// Mapper implementation for the Sv39 virtual memory scheme.
+///
+/// Sv39 virtual addresses consist of a 12 bit page offset and three 9
+/// bit VPN fields, giving three page table levels with 512 eight-byte
+/// entries each.
+pub type Sv39Mapper<AllocFrame, FreeFrame, TranslateAddress> =
+    SvMapper<3, AllocFrame, FreeFrame, TranslateAddress>;
+
+#[test]
+fn map_tables() {
+    unsafe {
+        let layout = std::alloc::Layout::from_size_align(0x100_0000, 0x1000).unwrap();
+        let memory_addr = std::alloc::alloc(layout.clone());
+
+        let root_addr = memory_addr as *mut PageTable;
+
+        let mut current_addr = 0x1000;
+
+        let mut mapper = Sv39Mapper::new(
+            root_addr,
+            || {
+                let result = current_addr;
+                current_addr += 0x1000;
+                Ok(result.into())
+            },
+            |_frame| Ok(()),
+            |phys_addr| (memory_addr as u64 + phys_addr.as_u64()).into(),
+        );
+
+        let entry = mapper.entry(0x4000_0000.into(), 1).unwrap();
+
+        let root = &mut *root_addr;
+        let pt2_addr = (memory_addr as u64 + root[1].address().as_u64()) as *mut PageTable;
+        let pt2 = &mut *pt2_addr;
+        let pt1_addr = (memory_addr as u64 + pt2[0].address().as_u64()) as *mut PageTable;
+
+        assert_eq!(root_addr.offset(1), pt2_addr);
+        assert_eq!(pt2_addr.offset(1), pt1_addr);
+        assert_eq!(pt1_addr, entry as *mut _ as _);
+
+        std::alloc::dealloc(memory_addr as _, layout);
+    }
+}
+
+#[test]
+fn unmap_reclaims_empty_tables() {
+    unsafe {
+        let layout = std::alloc::Layout::from_size_align(0x100_0000, 0x1000).unwrap();
+        let memory_addr = std::alloc::alloc(layout.clone());
+
+        let root_addr = memory_addr as *mut PageTable;
+        (&mut *root_addr).clear();
+
+        let mut current_addr = 0x1000;
+        let mut freed = Vec::new();
+
+        let mut mapper = Sv39Mapper::new(
+            root_addr,
+            || {
+                let result = current_addr;
+                current_addr += 0x1000;
+                Ok(result.into())
+            },
+            |frame: PhysicalAddress| {
+                freed.push(frame.as_u64());
+                Ok(())
+            },
+            |phys_addr| (memory_addr as u64 + phys_addr.as_u64()).into(),
+        );
+
+        // Deliberately not aligned to any huge page boundary, so this
+        // also exercises reclaim()'s internal re-alignment of virt_addr
+        // before it descends into entry().
+        let virt_addr: VirtualAddress = 0x20_3000.into();
+        mapper
+            .map(virt_addr, 0x4242000.into(), 1, |e| {
+                e.set_bit(Bit::Read);
+            })
+            .unwrap();
+        let (phys_addr, _level) = mapper.unmap(virt_addr).unwrap();
+
+        assert_eq!(phys_addr.as_u64(), 0x4242000);
+        // The two now-empty intermediate tables (pt2, pt1) are
+        // reclaimed, but not the root table passed in by the caller.
+        assert_eq!(freed.len(), 2);
+
+        std::alloc::dealloc(memory_addr as _, layout);
+    }
+}
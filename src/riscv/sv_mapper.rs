@@ -0,0 +1,183 @@
+use super::*;
+
+// The actual flush instructions are gated behind the `flush` feature and
+// `target_arch = "riscv64"` so that this crate still builds and tests on
+// hosts that can't execute them (e.g. running the test suite on a
+// non-riscv64 target). Both modules are always compiled together (see
+// `lib.rs`), so the arch guard is required in addition to the feature
+// gate -- otherwise enabling `flush` for one architecture would try to
+// assemble the other architecture's asm.
+
+/// Invalidate the cached translation for a single virtual address.
+#[cfg(all(feature = "flush", target_arch = "riscv64"))]
+unsafe fn sfence_vma(virt_addr: VirtualAddress) {
+    core::arch::asm!("sfence.vma {}, x0", in(reg) virt_addr.as_u64(), options(nostack, preserves_flags));
+}
+
+#[cfg(not(all(feature = "flush", target_arch = "riscv64")))]
+unsafe fn sfence_vma(_virt_addr: VirtualAddress) {}
+
+/// Invalidate all cached translations.
+#[cfg(all(feature = "flush", target_arch = "riscv64"))]
+unsafe fn sfence_vma_all() {
+    core::arch::asm!("sfence.vma", options(nostack, preserves_flags));
+}
+
+#[cfg(not(all(feature = "flush", target_arch = "riscv64")))]
+unsafe fn sfence_vma_all() {}
+
+/// Shared Sv39/Sv48 mapper implementation, parameterized over `LEVELS`
+/// (3 for Sv39, 4 for Sv48; see [`Sv39Mapper`](super::Sv39Mapper) and
+/// [`Sv48Mapper`](super::Sv48Mapper)). Both schemes walk the same 9
+/// bit-per-level, 512-entry page tables and differ only in how many
+/// levels deep that walk goes and where the sign-extension boundary of
+/// a virtual address falls -- both of which are derived from `LEVELS`
+/// alone, so a single generic implementation covers both without the
+/// two schemes being able to drift out of sync with each other.
+pub struct SvMapper<const LEVELS: u8, AllocFrame, FreeFrame, TranslateAddress>
+where
+    AllocFrame: FnMut() -> Result<PhysicalAddress>,
+    FreeFrame: FnMut(PhysicalAddress) -> Result<()>,
+    TranslateAddress: FnMut(PhysicalAddress) -> VirtualAddress,
+{
+    root: *mut PageTable,
+    alloc_frame: AllocFrame,
+    free_frame: FreeFrame,
+    translate_address: TranslateAddress,
+    batch_mode: bool,
+}
+
+impl<const LEVELS: u8, AllocFrame, FreeFrame, TranslateAddress>
+    SvMapper<LEVELS, AllocFrame, FreeFrame, TranslateAddress>
+where
+    AllocFrame: FnMut() -> Result<PhysicalAddress>,
+    FreeFrame: FnMut(PhysicalAddress) -> Result<()>,
+    TranslateAddress: FnMut(PhysicalAddress) -> VirtualAddress,
+{
+    /// Create a new mapper object.
+    ///
+    /// # Safety
+    ///
+    /// Safety assumptions:
+    /// * `root` is a pointer to a valid free memory page
+    /// * `alloc_frame` returns pointers to valid free memory pages
+    /// * `free_frame` accepts frames previously returned by `alloc_frame`
+    /// * `translate_address` correctly translates physical addresses to virtual addresses
+    pub unsafe fn new(
+        root: *mut PageTable,
+        alloc_frame: AllocFrame,
+        free_frame: FreeFrame,
+        translate_address: TranslateAddress,
+    ) -> Self {
+        assert_eq!(root as u64 % crate::level_size(1), 0);
+        SvMapper {
+            root,
+            alloc_frame,
+            free_frame,
+            translate_address,
+            batch_mode: false,
+        }
+    }
+
+    unsafe fn ensure_subtable(&mut self, entry: &mut Entry) -> Result<()> {
+        if !entry.bit(Bit::Valid) {
+            let frame = (self.alloc_frame)()?;
+            let addr = (self.translate_address)(frame);
+            let table = &mut *(addr.as_u64() as *mut PageTable);
+            table.clear();
+            entry.set_address(frame);
+
+            // Mark the table valid, but leave R/W/X clear so it is
+            // recognized as a pointer to the next level rather than a
+            // leaf descriptor.
+            entry.set_bit(Bit::Valid);
+            Ok(())
+        } else if entry.is_leaf() {
+            Err(Error::Overlap)
+        } else {
+            Ok(())
+        }
+    }
+
+    unsafe fn descend_entry(&mut self, entry: &mut Entry) -> Result<&'static mut PageTable> {
+        self.ensure_subtable(entry)?;
+        let phys_addr = entry.address();
+        let virt_addr = (self.translate_address)(phys_addr);
+        Ok(&mut *(virt_addr.as_u64() as *mut PageTable))
+    }
+}
+
+impl<const LEVELS: u8, AllocFrame, FreeFrame, TranslateAddress> Mapper
+    for SvMapper<LEVELS, AllocFrame, FreeFrame, TranslateAddress>
+where
+    AllocFrame: FnMut() -> Result<PhysicalAddress>,
+    FreeFrame: FnMut(PhysicalAddress) -> Result<()>,
+    TranslateAddress: FnMut(PhysicalAddress) -> VirtualAddress,
+{
+    type Entry = Entry;
+
+    const LEVELS: u8 = LEVELS;
+
+    unsafe fn entry(&mut self, virt_addr: VirtualAddress, level: u8) -> Result<&'static mut Entry> {
+        assert!(level >= 1 && level <= Self::LEVELS);
+        assert!(virt_addr.as_u64() % crate::level_size(level) == 0);
+
+        // Sv39/Sv48 addresses are sign-extended from the top VPN bit
+        // upwards; mask that off so the indices below come purely from
+        // the low, architecturally-significant bits.
+        let addr_bits = 12 + 9 * Self::LEVELS as u32;
+        let masked = virt_addr.as_u64() & ((1u64 << addr_bits) - 1);
+
+        let mut table = &mut *self.root;
+        let mut cur_level = Self::LEVELS;
+        loop {
+            let shift = 12 + 9 * (cur_level as u32 - 1);
+            let idx = (masked >> shift) & 0x1ff;
+            let entry = &mut table[idx as usize];
+            if cur_level == level {
+                return Ok(entry);
+            }
+            table = self.descend_entry(entry)?;
+            cur_level -= 1;
+        }
+    }
+
+    unsafe fn reclaim(&mut self, virt_addr: VirtualAddress, level: u8) -> Result<bool> {
+        let aligned = VirtualAddress::new(virt_addr.as_u64() & !(crate::level_size(level) - 1));
+        let entry = self.entry(aligned, level)?;
+        if !entry.bit(Bit::Valid) || entry.is_leaf() {
+            return Ok(false);
+        }
+
+        let phys_addr = entry.address();
+        let table_addr = (self.translate_address)(phys_addr);
+        let table = &mut *(table_addr.as_u64() as *mut PageTable);
+        if table.entries.iter().any(|e| e.bit(Bit::Valid)) {
+            return Ok(false);
+        }
+
+        entry.clear();
+        (self.free_frame)(phys_addr)?;
+        Ok(true)
+    }
+
+    fn flush_enabled(&self) -> bool {
+        !self.batch_mode
+    }
+
+    fn set_batch_mode(&mut self, batch: bool) {
+        self.batch_mode = batch;
+    }
+
+    fn invalidate(&mut self, virt_addr: VirtualAddress) {
+        unsafe {
+            sfence_vma(virt_addr);
+        }
+    }
+
+    fn flush_all(&mut self) {
+        unsafe {
+            sfence_vma_all();
+        }
+    }
+}
@@ -132,6 +132,44 @@ impl Entry {
     }
 }
 
+impl PageEntry for Entry {
+    fn clear(&mut self) {
+        Entry::clear(self);
+    }
+
+    fn address(&self) -> PhysicalAddress {
+        Entry::address(self)
+    }
+
+    fn set_address(&mut self, address: PhysicalAddress) -> &mut Self {
+        Entry::set_address(self, address)
+    }
+
+    fn is_present(&self) -> bool {
+        self.bit(Bit::Present)
+    }
+
+    fn set_present(&mut self, present: bool) -> &mut Self {
+        if present {
+            self.set_bit(Bit::Present)
+        } else {
+            self.unset_bit(Bit::Present)
+        }
+    }
+
+    fn is_huge(&self) -> bool {
+        self.bit(Bit::Huge)
+    }
+
+    fn set_huge(&mut self, huge: bool) -> &mut Self {
+        if huge {
+            self.set_bit(Bit::Huge)
+        } else {
+            self.unset_bit(Bit::Huge)
+        }
+    }
+}
+
 #[test]
 fn int_consistency() {
     let mut entry = Entry::new();